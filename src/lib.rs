@@ -3,29 +3,32 @@
 //! The implementation is based on the Python script provided in the RP2040 SDK:
 //! $PICO_SDK/src/rp2_common/hardware_clocks/scripts/vcocalc.py
 //!
-//! The macro takes a frequency (in kHz) as a literal and expands to an expression
-//! of type `Option<PLLConfig>`.
+//! The macro takes a frequency (in kHz) as a literal, or a keyword form that also
+//! lets the caller override the crystal and search limits (see `pll_config!` below),
+//! and expands to an expression of type `Option<PLLConfig>`.
 //!
 //! The algorithm searches over an expanded parameter space (REFDIV, FBDIV, PD1, and PD2)
-//! using hard-coded defaults (e.g. a 12 MHz input, minimum reference frequency 5 MHz,
+//! using defaults (e.g. a 12 MHz input, minimum reference frequency 5 MHz,
 //! VCO limits between 750 and 1600 MHz) and selects the configuration with the smallest
 //! error relative to the requested output frequency (converted from kHz to MHz).
 
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, LitInt};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, Ident, LitInt, Token};
 
-// The defaultts are:
+// The defaults are:
 // * 12 MHz input,
 // * 5 MHz minimum reference frequency,
 // * VCO between 750 and 1600 MHz,
 // * no locked REFDIV,
 // * and default tie-break aka prefer the higher VCO.
 
-const XOSC_MHZ: f64 = 12.0;
-const REF_MIN: f64 = 5.0;
-const VCO_MIN: f64 = 750.0;
-const VCO_MAX: f64 = 1600.0;
+const XOSC_KHZ: u64 = 12_000;
+const REF_MIN_KHZ: u64 = 5_000;
+const VCO_MIN_KHZ: u64 = 750_000;
+const VCO_MAX_KHZ: u64 = 1_600_000;
 const LOW_VCO: bool = false;
 const LOCKED_REFDIV: Option<u8> = None;
 
@@ -58,7 +61,9 @@ mod pll {
     /// * `requested_mhz` - The desired output frequency (e.g. 480.0).
     /// * `vco_min`       - Minimum allowed VCO frequency (e.g. 750.0).
     /// * `vco_max`       - Maximum allowed VCO frequency (e.g. 1600.0).
-    /// * `ref_min`       - Minimum allowed reference frequency (e.g. 5.0).
+    /// * `ref_min`       - Minimum allowed reference frequency (e.g. 5.0); enforced
+    ///                     per-candidate, so a `locked_refdiv` that violates it
+    ///                     yields no candidates rather than an out-of-spec config.
     /// * `locked_refdiv` - If Some(n), restricts the search to REFDIV == n.
     /// * `low_vco`       - If true, among equally good solutions prefer the one with a lower VCO frequency;
     ///                     otherwise, prefer higher VCO.
@@ -102,15 +107,26 @@ mod pll {
         let mut best_margin = requested_mhz;
 
         for refdiv in refdiv_iter {
+            // Reject any REFDIV (including a caller-locked one) that starves the
+            // reference input below `ref_min`; the HAL's `check_sys_clock_khz`
+            // enforces this floor at runtime, so we must not emit a config that
+            // violates it.
+            let ref_freq = input_mhz / (refdiv as f64);
+            if ref_freq < ref_min {
+                continue;
+            }
+
             for fbdiv in fbdiv_range.clone() {
                 // Compute VCO in MHz: vco = (input_mhz / refdiv) * fbdiv.
                 let vco = (input_mhz / (refdiv as f64)) * (fbdiv as f64);
                 if vco < vco_min || vco > vco_max {
                     continue;
                 }
-                // Loop over post divider combinations.
+                // Loop over post divider combinations. Only pd1 >= pd2 is generated
+                // here, since the HAL requires POST_DIV1 >= POST_DIV2 and the product
+                // (and hence the achieved output frequency) is the same either way.
                 for pd2 in postdiv_range.clone() {
-                    for pd1 in postdiv_range.clone() {
+                    for pd1 in pd2..=7 {
                         let divider = (pd1 * pd2) as f64;
                         // Check that the VCO (scaled to kHz) divides exactly by the divider.
                         // (This ensures that the achieved output frequency is an integer value when computed in kHz.)
@@ -157,6 +173,147 @@ mod pll {
             }
         })
     }
+
+    /// Optimization objective used to break ties between candidates that hit the
+    /// requested frequency equally well. Generalizes the `low_vco` flag of
+    /// `find_pll_config_extended` into a user-selectable strategy.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[allow(clippy::enum_variant_names)]
+    pub enum SelectionStrategy {
+        /// Prefer the smallest frequency error; among equally accurate candidates,
+        /// prefer the higher VCO frequency (matches `find_pll_config_extended` with
+        /// `low_vco: false`).
+        MinError,
+        /// Prefer the smallest frequency error; among equally accurate candidates,
+        /// prefer the lower VCO frequency (draws less power).
+        MinVco,
+        /// Prefer the smallest frequency error; among equally accurate candidates,
+        /// prefer the smallest FBDIV.
+        MinFbdiv,
+    }
+
+    /// A single ranked candidate kept while searching; converted to a
+    /// `PLLConfigExtended` once the search finishes.
+    struct Candidate {
+        margin: f64,
+        out: f64,
+        vco: f64,
+        fbdiv: u16,
+        post_div1: u8,
+        post_div2: u8,
+        refdiv: u8,
+    }
+
+    /// Returns `true` if `a` should be ranked ahead of `b` under `strategy`.
+    fn ranks_above(a: &Candidate, b: &Candidate, strategy: SelectionStrategy) -> bool {
+        if (a.margin - b.margin).abs() > 1e-9 {
+            return a.margin < b.margin;
+        }
+        match strategy {
+            SelectionStrategy::MinError => a.vco > b.vco,
+            SelectionStrategy::MinVco => a.vco < b.vco,
+            SelectionStrategy::MinFbdiv => a.fbdiv < b.fbdiv,
+        }
+    }
+
+    /// Finds the top `n` PLL configurations by searching over the same expanded
+    /// parameter space as `find_pll_config_extended`, sorted best-first by margin,
+    /// with ties between equally accurate candidates broken according to
+    /// `strategy`. Unlike `find_pll_config_extended`, which keeps only a single
+    /// best candidate, this keeps a bounded sorted buffer of up to `n` candidates
+    /// during the search. Useful when the caller wants to trade a small frequency
+    /// error for e.g. lower VCO (and hence lower power).
+    ///
+    /// See `find_pll_config_extended` for the meaning of `input_mhz`, `requested_mhz`,
+    /// `vco_min`, `vco_max`, `ref_min`, and `locked_refdiv`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn find_pll_configs_ranked(
+        input_mhz: f64,
+        requested_mhz: f64,
+        vco_min: f64,
+        vco_max: f64,
+        ref_min: f64,
+        locked_refdiv: Option<u8>,
+        strategy: SelectionStrategy,
+        n: usize,
+    ) -> Vec<PLLConfigExtended> {
+        let fbdiv_range = 16..=320; // valid FBDIV values
+        let postdiv_range = 1..=7; // valid post divider values
+
+        let refdiv_min: u8 = 1;
+        let refdiv_max: u8 = 63;
+        let max_possible = ((input_mhz / ref_min).floor() as u8).min(refdiv_max);
+        let max_refdiv = if max_possible < refdiv_min {
+            refdiv_min
+        } else {
+            max_possible
+        };
+
+        let refdiv_iter: Box<dyn Iterator<Item = u8>> = if let Some(lock) = locked_refdiv {
+            Box::new(std::iter::once(lock))
+        } else {
+            Box::new(refdiv_min..=max_refdiv)
+        };
+
+        let mut buffer: Vec<Candidate> = Vec::new();
+
+        for refdiv in refdiv_iter {
+            let ref_freq = input_mhz / (refdiv as f64);
+            if ref_freq < ref_min {
+                continue;
+            }
+
+            for fbdiv in fbdiv_range.clone() {
+                let vco = (input_mhz / (refdiv as f64)) * (fbdiv as f64);
+                if vco < vco_min || vco > vco_max {
+                    continue;
+                }
+
+                for pd2 in postdiv_range.clone() {
+                    for pd1 in pd2..=7 {
+                        let divider = (pd1 * pd2) as f64;
+                        if (vco * 1000.0) % divider != 0.0 {
+                            continue;
+                        }
+                        let out = vco / divider;
+                        let margin = (out - requested_mhz).abs();
+                        let candidate = Candidate {
+                            margin,
+                            out,
+                            vco,
+                            fbdiv,
+                            post_div1: pd1,
+                            post_div2: pd2,
+                            refdiv,
+                        };
+
+                        if buffer.len() < n {
+                            let pos = buffer.partition_point(|c| ranks_above(c, &candidate, strategy));
+                            buffer.insert(pos, candidate);
+                        } else if n > 0
+                            && ranks_above(&candidate, buffer.last().unwrap(), strategy)
+                        {
+                            let pos = buffer.partition_point(|c| ranks_above(c, &candidate, strategy));
+                            buffer.insert(pos, candidate);
+                            buffer.pop();
+                        }
+                    }
+                }
+            }
+        }
+
+        buffer
+            .into_iter()
+            .map(|c| PLLConfigExtended {
+                vco_freq: HertzU32((c.vco * 1_000_000.0).round() as u32),
+                refdiv: c.refdiv,
+                fbdiv: c.fbdiv,
+                post_div1: c.post_div1,
+                post_div2: c.post_div2,
+                sys_clk_mhz: c.out,
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -259,13 +416,18 @@ mod tests {
             },
         ];
 
+        let xosc_mhz = XOSC_KHZ as f64 / 1000.0;
+        let vco_min_mhz = VCO_MIN_KHZ as f64 / 1000.0;
+        let vco_max_mhz = VCO_MAX_KHZ as f64 / 1000.0;
+        let ref_min_mhz = REF_MIN_KHZ as f64 / 1000.0;
+
         for tc in &test_cases {
             let config = find_pll_config_extended(
-                XOSC_MHZ,
+                xosc_mhz,
                 tc.requested_mhz,
-                VCO_MIN,
-                VCO_MAX,
-                REF_MIN,
+                vco_min_mhz,
+                vco_max_mhz,
+                ref_min_mhz,
                 LOCKED_REFDIV,
                 LOW_VCO,
             )
@@ -306,7 +468,7 @@ mod tests {
             );
 
             // Also check that the computed VCO equals the expected value.
-            let computed_vco = XOSC_MHZ / (config.refdiv as f64) * (config.fbdiv as f64);
+            let computed_vco = xosc_mhz / (config.refdiv as f64) * (config.fbdiv as f64);
             assert!(
                 (computed_vco - tc.expected_vco).abs() < 1e-6,
                 "VCO mismatch for {} MHz requested: got {} MHz, expected {} MHz",
@@ -316,10 +478,211 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_find_pll_configs_ranked_strategies() {
+        use crate::pll::{find_pll_configs_ranked, SelectionStrategy};
+
+        // 100 MHz is exactly reachable many ways from a 12 MHz crystal, so every
+        // candidate below ties on margin == 0 and the chosen strategy alone
+        // determines the ranking.
+        let min_error =
+            find_pll_configs_ranked(12.0, 100.0, 750.0, 1600.0, 5.0, None, SelectionStrategy::MinError, 5);
+        let min_vco =
+            find_pll_configs_ranked(12.0, 100.0, 750.0, 1600.0, 5.0, None, SelectionStrategy::MinVco, 5);
+        let min_fbdiv =
+            find_pll_configs_ranked(12.0, 100.0, 750.0, 1600.0, 5.0, None, SelectionStrategy::MinFbdiv, 5);
+
+        assert_eq!(min_error.len(), 5);
+        assert_eq!(min_vco.len(), 5);
+        assert_eq!(min_fbdiv.len(), 5);
+
+        // MinError tie-breaks toward the highest VCO; the buffer stays sorted by
+        // descending VCO among the tied (zero-margin) candidates.
+        assert_eq!(min_error[0].vco_freq.0, 1_500_000_000);
+        assert!(min_error.windows(2).all(|w| w[0].vco_freq.0 >= w[1].vco_freq.0));
+
+        // MinVco tie-breaks toward the lowest VCO (less power).
+        assert_eq!(min_vco[0].vco_freq.0, 900_000_000);
+        assert!(min_vco.windows(2).all(|w| w[0].vco_freq.0 <= w[1].vco_freq.0));
+
+        // MinFbdiv tie-breaks toward the smallest FBDIV.
+        assert_eq!(min_fbdiv[0].fbdiv, 75);
+        assert!(min_fbdiv.windows(2).all(|w| w[0].fbdiv <= w[1].fbdiv));
+
+        // A bounded buffer of size `n` keeps only the best `n` found so far,
+        // evicting the worst entry as better candidates turn up during the search.
+        let top2 =
+            find_pll_configs_ranked(12.0, 100.0, 750.0, 1600.0, 5.0, None, SelectionStrategy::MinVco, 2);
+        assert_eq!(top2.len(), 2);
+        assert_eq!(top2[0].vco_freq.0, min_vco[0].vco_freq.0);
+        assert_eq!(top2[1].vco_freq.0, min_vco[1].vco_freq.0);
+
+        // n == 0 keeps nothing.
+        assert!(find_pll_configs_ranked(
+            12.0,
+            100.0,
+            750.0,
+            1600.0,
+            5.0,
+            None,
+            SelectionStrategy::MinError,
+            0
+        )
+        .is_empty());
+    }
+}
+
+/// The value side of a `key = value` argument: either an integer (e.g. `freq_khz
+/// = 480000`) or a bare identifier (e.g. `strategy = min_vco`).
+enum ArgValue {
+    Int(u64),
+    Ident(String),
+}
+
+/// A single `key = value` argument of the keyword form of `pll_config!`.
+struct KeywordArg {
+    key: Ident,
+    value: ArgValue,
 }
 
-/// The `pll_config` proc macro takes a frequency in kilohertz as a literal and
-/// expands to an expression of type `Option<PLLConfig>`.
+impl Parse for KeywordArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key: Ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value = if input.peek(LitInt) {
+            let lit: LitInt = input.parse()?;
+            ArgValue::Int(lit.base10_parse()?)
+        } else {
+            let ident: Ident = input.parse()?;
+            ArgValue::Ident(ident.to_string())
+        };
+        Ok(KeywordArg { key, value })
+    }
+}
+
+/// Extracts an integer value from a keyword argument, erroring if it was given
+/// as an identifier instead (e.g. `freq_khz = min_vco`).
+fn expect_int(arg: &KeywordArg) -> syn::Result<u64> {
+    match arg.value {
+        ArgValue::Int(v) => Ok(v),
+        ArgValue::Ident(_) => Err(syn::Error::new(
+            arg.key.span(),
+            format!("`{}` expects an integer value", arg.key),
+        )),
+    }
+}
+
+/// Parses the `strategy` keyword argument's identifier into a `SelectionStrategy`.
+fn parse_strategy(arg: &KeywordArg) -> syn::Result<pll::SelectionStrategy> {
+    match &arg.value {
+        ArgValue::Ident(ident) => match ident.as_str() {
+            "min_error" => Ok(pll::SelectionStrategy::MinError),
+            "min_vco" => Ok(pll::SelectionStrategy::MinVco),
+            "min_fbdiv" => Ok(pll::SelectionStrategy::MinFbdiv),
+            other => Err(syn::Error::new(
+                arg.key.span(),
+                format!(
+                    "unknown `strategy` value `{other}`; expected `min_error`, `min_vco`, or `min_fbdiv`"
+                ),
+            )),
+        },
+        ArgValue::Int(_) => Err(syn::Error::new(
+            arg.key.span(),
+            "`strategy` expects an identifier (e.g. `min_vco`), not an integer",
+        )),
+    }
+}
+
+/// Parsed arguments of `pll_config!`, in kHz throughout.
+///
+/// `freq_khz` has no default and must always be supplied; the rest fall back to
+/// the crate's usual 12 MHz crystal assumptions when omitted. `tol_khz` has no
+/// default either: when absent, an out-of-spec result is returned as `Some(...)`
+/// like before; when present, it turns one into a `compile_error!`.
+struct PllConfigArgs {
+    freq_khz: u64,
+    xosc_khz: u64,
+    vco_min_khz: u64,
+    vco_max_khz: u64,
+    ref_min_khz: u64,
+    tol_khz: Option<u64>,
+    strategy: Option<pll::SelectionStrategy>,
+}
+
+impl Parse for PllConfigArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // Bare literal form: `pll_config!(480000)`.
+        if input.peek(LitInt) {
+            let freq: LitInt = input.parse()?;
+            return Ok(PllConfigArgs {
+                freq_khz: freq.base10_parse()?,
+                xosc_khz: XOSC_KHZ,
+                vco_min_khz: VCO_MIN_KHZ,
+                vco_max_khz: VCO_MAX_KHZ,
+                ref_min_khz: REF_MIN_KHZ,
+                tol_khz: None,
+                strategy: None,
+            });
+        }
+
+        // Keyword form: `pll_config!(freq_khz = 480000, xosc_khz = 16000, ...)`.
+        let args = Punctuated::<KeywordArg, Token![,]>::parse_terminated(input)?;
+
+        let mut freq_khz = None;
+        let mut xosc_khz = XOSC_KHZ;
+        let mut vco_min_khz = VCO_MIN_KHZ;
+        let mut vco_max_khz = VCO_MAX_KHZ;
+        let mut ref_min_khz = REF_MIN_KHZ;
+        let mut tol_khz = None;
+        let mut strategy = None;
+
+        for arg in args {
+            match arg.key.to_string().as_str() {
+                "freq_khz" => freq_khz = Some(expect_int(&arg)?),
+                "xosc_khz" => xosc_khz = expect_int(&arg)?,
+                "vco_min_khz" => vco_min_khz = expect_int(&arg)?,
+                "vco_max_khz" => vco_max_khz = expect_int(&arg)?,
+                "ref_min_khz" => ref_min_khz = expect_int(&arg)?,
+                "tol_khz" => tol_khz = Some(expect_int(&arg)?),
+                "strategy" => strategy = Some(parse_strategy(&arg)?),
+                other => {
+                    return Err(syn::Error::new(
+                        arg.key.span(),
+                        format!("unknown `pll_config!` argument `{other}`"),
+                    ))
+                }
+            }
+        }
+
+        let freq_khz = freq_khz
+            .ok_or_else(|| syn::Error::new(input.span(), "`pll_config!` requires `freq_khz`"))?;
+
+        Ok(PllConfigArgs {
+            freq_khz,
+            xosc_khz,
+            vco_min_khz,
+            vco_max_khz,
+            ref_min_khz,
+            tol_khz,
+            strategy,
+        })
+    }
+}
+
+/// The `pll_config` proc macro takes a frequency in kilohertz as a literal, or the
+/// keyword form `freq_khz = ..., xosc_khz = ..., vco_min_khz = ..., vco_max_khz = ...,
+/// ref_min_khz = ..., tol_khz = ..., strategy = ...` to override the crystal, search
+/// limits, accuracy requirement, and tie-break objective, and expands to an
+/// expression of type `Option<PLLConfig>`. Every keyword argument other than
+/// `freq_khz` is optional. `xosc_khz`, `vco_min_khz`, `vco_max_khz`, and
+/// `ref_min_khz` default to this crate's usual 12 MHz crystal assumptions when
+/// omitted. `tol_khz`, when given, turns a result whose achieved frequency is
+/// further than `tol_khz` from `freq_khz` into a `compile_error!` instead of a
+/// `Some(...)` that would only be discovered wrong at runtime; when omitted, the
+/// closest achievable config is always returned. `strategy` picks which equally
+/// accurate candidate wins: `min_error` (the default), `min_vco`, or `min_fbdiv` —
+/// see `pll::SelectionStrategy`.
 ///
 /// # Example
 ///
@@ -330,32 +693,96 @@ mod tests {
 /// // 480000 represents 480 MHz (i.e. 480000 kHz)
 /// let config = pll_config!(480000);
 /// const CONFIG: PLLConfig = pll_config!(480000).unwrap();
+///
+/// // A 16 MHz crystal.
+/// const CONFIG_16M: PLLConfig = pll_config!(freq_khz = 480000, xosc_khz = 16000).unwrap();
+/// assert_eq!(CONFIG_16M.vco_freq, fugit::HertzU32::Hz(1_440_000_000));
+///
+/// // Within tolerance, so this still builds.
+/// const CONFIG_EXACT: PLLConfig = pll_config!(freq_khz = 133000, tol_khz = 1).unwrap();
+/// assert_eq!(CONFIG_EXACT.vco_freq, fugit::HertzU32::Hz(1_596_000_000));
+///
+/// // Prefer the lowest-power (lowest VCO) candidate among equally accurate ones.
+/// const CONFIG_LOW_POWER: PLLConfig =
+///     pll_config!(freq_khz = 100000, strategy = min_vco).unwrap();
+/// assert_eq!(CONFIG_LOW_POWER.vco_freq, fugit::HertzU32::Hz(900_000_000));
+/// ```
+///
+/// A `tol_khz` tighter than any achievable result is a compile error rather than
+/// a silently wrong `PLLConfig`:
+///
+/// ```compile_fail
+/// use pico_pll_config::pll_config;
+/// use rp2040_hal::pll::PLLConfig;
+///
+/// // 133001 kHz is not reachable at all from a 12 MHz crystal, let alone within 0 kHz.
+/// const CONFIG: PLLConfig = pll_config!(freq_khz = 133001, tol_khz = 0).unwrap();
 /// ```
 #[proc_macro]
 pub fn pll_config(input: TokenStream) -> TokenStream {
-    // Parse the input as an integer literal.
-    let input_lit = parse_macro_input!(input as LitInt);
-    let freq_khz: u64 = input_lit.base10_parse().expect("Invalid integer literal");
-
-    let requested_mhz = freq_khz as f64 / 1000.0;
-    let result = pll::find_pll_config_extended(
-        XOSC_MHZ,
-        requested_mhz,
-        VCO_MIN,
-        VCO_MAX,
-        REF_MIN,
-        LOCKED_REFDIV,
-        LOW_VCO,
-    );
+    let args = parse_macro_input!(input as PllConfigArgs);
+
+    let requested_mhz = args.freq_khz as f64 / 1000.0;
+    let xosc_mhz = args.xosc_khz as f64 / 1000.0;
+    let vco_min_mhz = args.vco_min_khz as f64 / 1000.0;
+    let vco_max_mhz = args.vco_max_khz as f64 / 1000.0;
+    let ref_min_mhz = args.ref_min_khz as f64 / 1000.0;
+
+    let result = if let Some(strategy) = args.strategy {
+        pll::find_pll_configs_ranked(
+            xosc_mhz,
+            requested_mhz,
+            vco_min_mhz,
+            vco_max_mhz,
+            ref_min_mhz,
+            LOCKED_REFDIV,
+            strategy,
+            1,
+        )
+        .into_iter()
+        .next()
+    } else {
+        pll::find_pll_config_extended(
+            xosc_mhz,
+            requested_mhz,
+            vco_min_mhz,
+            vco_max_mhz,
+            ref_min_mhz,
+            LOCKED_REFDIV,
+            LOW_VCO,
+        )
+    };
+
+    if let (Some(config), Some(tol_khz)) = (&result, args.tol_khz) {
+        let achieved_khz = (config.sys_clk_mhz * 1000.0).round() as i64;
+        let margin_khz = (achieved_khz - args.freq_khz as i64).abs();
+        if margin_khz > tol_khz as i64 {
+            let message = format!(
+                "pll_config!: no configuration for {} kHz within {} kHz tolerance \
+                 (nearest achievable: {} kHz)",
+                args.freq_khz, tol_khz, achieved_khz
+            );
+            return TokenStream::from(quote! { compile_error!(#message) });
+        }
+    }
 
-    let expanded = if let Some(ref config) = result {
-        let vco_mhz = config.vco_freq.0 / 1_000_000;
+    TokenStream::from(pll_config_expr(&result))
+}
+
+/// Expands an `Option<PLLConfigExtended>` into the `Option<rp2040_hal::pll::PLLConfig>`
+/// expression emitted by `pll_config!` and `pll_config_dual!`.
+fn pll_config_expr(config: &Option<pll::PLLConfigExtended>) -> proc_macro2::TokenStream {
+    if let Some(config) = config {
+        // Emit the exact VCO frequency in Hz: truncating to whole MHz would
+        // corrupt the config for any crystal (e.g. 12.288 MHz) whose VCO lands
+        // on a fractional MHz value.
+        let vco_hz = config.vco_freq.0;
         let refdiv = config.refdiv;
         let post_div1 = config.post_div1;
         let post_div2 = config.post_div2;
         quote! {
             Some(rp2040_hal::pll::PLLConfig {
-                vco_freq: fugit::HertzU32::MHz(#vco_mhz),
+                vco_freq: fugit::HertzU32::Hz(#vco_hz),
                 refdiv: #refdiv,
                 post_div1: #post_div1,
                 post_div2: #post_div2,
@@ -363,7 +790,118 @@ pub fn pll_config(input: TokenStream) -> TokenStream {
         }
     } else {
         quote! { None }
-    };
+    }
+}
+
+/// Parsed arguments of `pll_config_dual!`, in kHz throughout.
+///
+/// `sys_khz` and `usb_khz` have no defaults and must always be supplied; the rest
+/// fall back to the crate's usual 12 MHz crystal assumptions when omitted.
+struct PllConfigDualArgs {
+    sys_khz: u64,
+    usb_khz: u64,
+    xosc_khz: u64,
+    vco_min_khz: u64,
+    vco_max_khz: u64,
+    ref_min_khz: u64,
+}
+
+impl Parse for PllConfigDualArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let args = Punctuated::<KeywordArg, Token![,]>::parse_terminated(input)?;
+
+        let mut sys_khz = None;
+        let mut usb_khz = None;
+        let mut xosc_khz = XOSC_KHZ;
+        let mut vco_min_khz = VCO_MIN_KHZ;
+        let mut vco_max_khz = VCO_MAX_KHZ;
+        let mut ref_min_khz = REF_MIN_KHZ;
+
+        for arg in args {
+            match arg.key.to_string().as_str() {
+                "sys_khz" => sys_khz = Some(expect_int(&arg)?),
+                "usb_khz" => usb_khz = Some(expect_int(&arg)?),
+                "xosc_khz" => xosc_khz = expect_int(&arg)?,
+                "vco_min_khz" => vco_min_khz = expect_int(&arg)?,
+                "vco_max_khz" => vco_max_khz = expect_int(&arg)?,
+                "ref_min_khz" => ref_min_khz = expect_int(&arg)?,
+                other => {
+                    return Err(syn::Error::new(
+                        arg.key.span(),
+                        format!("unknown `pll_config_dual!` argument `{other}`"),
+                    ))
+                }
+            }
+        }
+
+        let sys_khz = sys_khz.ok_or_else(|| {
+            syn::Error::new(input.span(), "`pll_config_dual!` requires `sys_khz`")
+        })?;
+        let usb_khz = usb_khz.ok_or_else(|| {
+            syn::Error::new(input.span(), "`pll_config_dual!` requires `usb_khz`")
+        })?;
+
+        Ok(PllConfigDualArgs {
+            sys_khz,
+            usb_khz,
+            xosc_khz,
+            vco_min_khz,
+            vco_max_khz,
+            ref_min_khz,
+        })
+    }
+}
+
+/// The `pll_config_dual` proc macro solves both RP2040 PLLs (`pll_sys` and
+/// `pll_usb`) against the same crystal in one const-evaluated site, expanding to
+/// a tuple `(Option<PLLConfig>, Option<PLLConfig>)` of `(pll_sys, pll_usb)`.
+/// Takes the keyword form `sys_khz = ..., usb_khz = ..., xosc_khz = ..., vco_min_khz
+/// = ..., vco_max_khz = ..., ref_min_khz = ...`; only `sys_khz` and `usb_khz` are
+/// required, the rest default the same way as in `pll_config!`.
+///
+/// # Example
+///
+/// ```rust
+/// use pico_pll_config::pll_config_dual;
+/// use rp2040_hal::pll::PLLConfig;
+///
+/// const PLLS: (Option<PLLConfig>, Option<PLLConfig>) =
+///     pll_config_dual!(sys_khz = 125000, usb_khz = 48000);
+///
+/// let (sys, usb) = PLLS;
+/// assert_eq!(sys.unwrap().vco_freq, fugit::HertzU32::Hz(1_500_000_000));
+/// assert_eq!(usb.unwrap().vco_freq, fugit::HertzU32::Hz(1_440_000_000));
+/// ```
+#[proc_macro]
+pub fn pll_config_dual(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as PllConfigDualArgs);
+
+    let xosc_mhz = args.xosc_khz as f64 / 1000.0;
+    let vco_min_mhz = args.vco_min_khz as f64 / 1000.0;
+    let vco_max_mhz = args.vco_max_khz as f64 / 1000.0;
+    let ref_min_mhz = args.ref_min_khz as f64 / 1000.0;
+
+    let sys_result = pll::find_pll_config_extended(
+        xosc_mhz,
+        args.sys_khz as f64 / 1000.0,
+        vco_min_mhz,
+        vco_max_mhz,
+        ref_min_mhz,
+        LOCKED_REFDIV,
+        LOW_VCO,
+    );
+    let usb_result = pll::find_pll_config_extended(
+        xosc_mhz,
+        args.usb_khz as f64 / 1000.0,
+        vco_min_mhz,
+        vco_max_mhz,
+        ref_min_mhz,
+        LOCKED_REFDIV,
+        LOW_VCO,
+    );
+
+    let sys_expr = pll_config_expr(&sys_result);
+    let usb_expr = pll_config_expr(&usb_result);
 
-    TokenStream::from(expanded)
+    TokenStream::from(quote! { (#sys_expr, #usb_expr) })
 }